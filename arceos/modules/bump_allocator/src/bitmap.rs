@@ -0,0 +1,192 @@
+use allocator::{AllocError, AllocResult, BaseAllocator, PageAllocator};
+use core::cmp::max;
+
+/// Number of `usize` words in the backing bitmap. With 64-bit words this covers
+/// up to `CAP_WORDS * 64` = 65536 pages (256 MiB of 4 KiB pages), enough for
+/// page-table and DMA frame bookkeeping on the early boot path.
+const CAP_WORDS: usize = 1024;
+
+/// Bits per bitmap word.
+const WORD_BITS: usize = usize::BITS as usize;
+
+/// Bitmap-backed page allocator.
+///
+/// Each managed page owns one bit in a fixed-capacity bitmap (`1` == allocated).
+/// Unlike the backward-growing bump scheme of [`EarlyAllocator`](crate::EarlyAllocator),
+/// arbitrary pages can be freed and later reused, so ArceOS can hand out and
+/// reclaim individual page frames for page tables and DMA buffers.
+pub struct BitmapPageAllocator<const PAGE_SIZE: usize> {
+    base: usize,
+    bits: [usize; CAP_WORDS],
+    total_pages: usize,
+    used_pages: usize,
+}
+
+impl<const PAGE_SIZE: usize> Default for BitmapPageAllocator<PAGE_SIZE> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const PAGE_SIZE: usize> BitmapPageAllocator<PAGE_SIZE> {
+    /// Maximum number of pages the fixed-capacity bitmap can track.
+    pub const CAPACITY_PAGES: usize = CAP_WORDS * WORD_BITS;
+
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            base: 0,
+            bits: [0; CAP_WORDS],
+            total_pages: 0,
+            used_pages: 0,
+        }
+    }
+
+    #[inline]
+    fn get(&self, page: usize) -> bool {
+        self.bits[page / WORD_BITS] & (1usize << (page % WORD_BITS)) != 0
+    }
+
+    #[inline]
+    fn set(&mut self, page: usize) {
+        self.bits[page / WORD_BITS] |= 1usize << (page % WORD_BITS);
+    }
+
+    #[inline]
+    fn clear(&mut self, page: usize) {
+        self.bits[page / WORD_BITS] &= !(1usize << (page % WORD_BITS));
+    }
+}
+
+impl<const PAGE_SIZE: usize> BaseAllocator for BitmapPageAllocator<PAGE_SIZE> {
+    /// Initialize over `[start_vaddr, start_vaddr + size)`.
+    ///
+    /// The bitmap has a fixed capacity of [`CAPACITY_PAGES`] pages. A region
+    /// larger than that cannot be represented; rather than silently leaving the
+    /// tail unmanaged, this asserts in debug builds. Callers managing more than
+    /// `CAPACITY_PAGES` frames must raise `CAP_WORDS` or split the region.
+    ///
+    /// [`CAPACITY_PAGES`]: Self::CAPACITY_PAGES
+    fn init(&mut self, start_vaddr: usize, size: usize) {
+        let pages = size / PAGE_SIZE;
+        debug_assert!(
+            pages <= Self::CAPACITY_PAGES,
+            "BitmapPageAllocator region exceeds bitmap capacity; tail would be unmanaged"
+        );
+        self.base = start_vaddr;
+        self.bits = [0; CAP_WORDS];
+        self.total_pages = pages.min(Self::CAPACITY_PAGES);
+        self.used_pages = 0;
+    }
+
+    fn add_memory(&mut self, _start_vaddr: usize, _size: usize) -> AllocResult {
+        Err(AllocError::NoMemory)
+    }
+}
+
+impl<const PAGE_SIZE: usize> PageAllocator for BitmapPageAllocator<PAGE_SIZE> {
+    const PAGE_SIZE: usize = PAGE_SIZE;
+
+    fn alloc_pages(&mut self, num_pages: usize, align_pow2: usize) -> AllocResult<usize> {
+        if num_pages == 0 {
+            return Err(AllocError::InvalidParam);
+        }
+        let align = max(PAGE_SIZE, 1usize << align_pow2);
+        if !align.is_power_of_two() {
+            return Err(AllocError::InvalidParam);
+        }
+        let align_pages = align / PAGE_SIZE;
+
+        // Scan for the first aligned run of `num_pages` consecutive clear bits.
+        let mut page = 0;
+        while page + num_pages <= self.total_pages {
+            if page % align_pages != 0 {
+                page += align_pages - (page % align_pages);
+                continue;
+            }
+            match (page..page + num_pages).find(|&p| self.get(p)) {
+                // A used page inside the run: restart just past it.
+                Some(used) => page = used + 1,
+                None => {
+                    for p in page..page + num_pages {
+                        self.set(p);
+                    }
+                    self.used_pages += num_pages;
+                    return Ok(self.base + page * PAGE_SIZE);
+                }
+            }
+        }
+        Err(AllocError::NoMemory)
+    }
+
+    fn dealloc_pages(&mut self, pos: usize, num_pages: usize) {
+        let start = (pos - self.base) / PAGE_SIZE;
+        for p in start..start + num_pages {
+            if self.get(p) {
+                self.clear(p);
+                self.used_pages -= 1;
+            }
+        }
+    }
+
+    #[inline]
+    fn total_pages(&self) -> usize {
+        self.total_pages
+    }
+
+    #[inline]
+    fn used_pages(&self) -> usize {
+        self.used_pages
+    }
+
+    #[inline]
+    fn available_pages(&self) -> usize {
+        self.total_pages - self.used_pages
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use super::*;
+    use allocator::PageAllocator;
+
+    const PAGE: usize = 4096;
+    // The bitmap never touches the managed pages, so a bare base address works.
+    const BASE: usize = 0x8000_0000;
+
+    #[test]
+    fn alloc_free_reuse() {
+        let mut a = BitmapPageAllocator::<PAGE>::new();
+        a.init(BASE, 16 * PAGE);
+        assert_eq!(a.total_pages(), 16);
+        assert_eq!(a.available_pages(), 16);
+
+        let p0 = a.alloc_pages(2, 0).unwrap();
+        let p1 = a.alloc_pages(2, 0).unwrap();
+        assert_eq!(p0, BASE);
+        assert_eq!(p1, BASE + 2 * PAGE);
+        assert_eq!(a.used_pages(), 4);
+
+        // Freeing the first run lets the next request reuse those exact frames.
+        a.dealloc_pages(p0, 2);
+        assert_eq!(a.used_pages(), 2);
+        let p2 = a.alloc_pages(2, 0).unwrap();
+        assert_eq!(p2, BASE);
+        assert_eq!(a.used_pages(), 4);
+    }
+
+    #[test]
+    fn respects_alignment() {
+        let mut a = BitmapPageAllocator::<PAGE>::new();
+        a.init(BASE, 16 * PAGE);
+
+        // Occupy the first frame so the next aligned run must skip ahead.
+        let _ = a.alloc_pages(1, 0).unwrap();
+        let align_pow2 = (4 * PAGE).trailing_zeros() as usize;
+        let p = a.alloc_pages(1, align_pow2).unwrap();
+        assert_eq!(p % (4 * PAGE), 0);
+        assert_eq!(p, BASE + 4 * PAGE);
+    }
+}
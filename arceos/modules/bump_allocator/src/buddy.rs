@@ -0,0 +1,339 @@
+use allocator::{AllocError, AllocResult, BaseAllocator, PageAllocator};
+use core::cmp::max;
+use core::ptr::NonNull;
+
+/// Highest supported order; order `k` manages blocks of `2^k` pages.
+/// Orders `0..=MAX_ORDER` are tracked, so the largest single block is
+/// `2^MAX_ORDER` pages.
+const MAX_ORDER: usize = 32;
+
+/// Intrusive free-list node, stored inside the first word of a free block.
+struct FreeNode {
+    next: Option<NonNull<FreeNode>>,
+}
+
+/// Binary-buddy page allocator.
+///
+/// Unlike [`EarlyAllocator`](crate::EarlyAllocator), whose page area can never
+/// be reclaimed, this allocator keeps `MAX_ORDER + 1` free lists where order
+/// `k` holds blocks of `2^k` pages. Allocation pops from the requested order or
+/// splits a larger block into buddies; deallocation coalesces a block with its
+/// buddy whenever both are free and of the same order, which keeps
+/// fragmentation in check. The free lists are singly linked and searched
+/// linearly (for an aligned block, and to unlink a buddy), so alloc and free
+/// cost grows with the length of the lists walked, not only with the order.
+///
+/// Free-list links live intrusively in the free pages themselves, so the
+/// allocator needs no external bookkeeping storage.
+pub struct BuddyPageAllocator<const PAGE_SIZE: usize> {
+    base: usize,
+    end: usize,
+    free_lists: [Option<NonNull<FreeNode>>; MAX_ORDER + 1],
+    total_pages: usize,
+    used_pages: usize,
+}
+
+impl<const PAGE_SIZE: usize> Default for BuddyPageAllocator<PAGE_SIZE> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const PAGE_SIZE: usize> BuddyPageAllocator<PAGE_SIZE> {
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            base: 0,
+            end: 0,
+            free_lists: [None; MAX_ORDER + 1],
+            total_pages: 0,
+            used_pages: 0,
+        }
+    }
+
+    /// Smallest order whose block covers `num_pages` pages.
+    #[inline]
+    fn order_for(num_pages: usize) -> usize {
+        let mut order = 0;
+        while (1usize << order) < num_pages {
+            order += 1;
+        }
+        order
+    }
+
+    /// Push the block starting at `addr` onto the free list of `order`.
+    ///
+    /// # Safety
+    /// `addr` must be the start of an unused, order-`order` block inside the
+    /// managed region.
+    unsafe fn push(&mut self, order: usize, addr: usize) {
+        let node = addr as *mut FreeNode;
+        (*node).next = self.free_lists[order];
+        self.free_lists[order] = NonNull::new(node);
+    }
+
+    #[inline]
+    fn align_up(addr: usize, align: usize) -> usize {
+        (addr + align - 1) & !(align - 1)
+    }
+
+    /// Split the order-`from` block at `block` down to order `to`, descending
+    /// toward `target` and pushing every unused half onto its free list.
+    ///
+    /// # Safety
+    /// `block` must be an unused, order-`from` block and `target` an aligned
+    /// order-`to` sub-block within it.
+    unsafe fn split_to(&mut self, mut from: usize, mut block: usize, to: usize, target: usize) {
+        while from > to {
+            from -= 1;
+            let mid = block + (1usize << from) * PAGE_SIZE;
+            if target < mid {
+                self.push(from, mid);
+            } else {
+                self.push(from, block);
+                block = mid;
+            }
+        }
+        debug_assert_eq!(block, target);
+    }
+
+    /// Remove the block at `addr` from the free list of `order`.
+    ///
+    /// Returns `true` if the block was present (i.e. the buddy was free).
+    fn remove(&mut self, order: usize, addr: usize) -> bool {
+        let mut link = &mut self.free_lists[order] as *mut Option<NonNull<FreeNode>>;
+        // SAFETY: we only dereference live nodes reachable from the list head.
+        unsafe {
+            while let Some(node) = *link {
+                if node.as_ptr() as usize == addr {
+                    *link = node.as_ref().next;
+                    return true;
+                }
+                link = &mut (*node.as_ptr()).next;
+            }
+        }
+        false
+    }
+
+    /// Offset of the buddy of the order-`order` block at `addr`, as an address.
+    #[inline]
+    fn buddy_of(&self, order: usize, addr: usize) -> usize {
+        self.base + ((addr - self.base) ^ ((1usize << order) * PAGE_SIZE))
+    }
+}
+
+impl<const PAGE_SIZE: usize> BaseAllocator for BuddyPageAllocator<PAGE_SIZE> {
+    fn init(&mut self, start_vaddr: usize, size: usize) {
+        self.base = start_vaddr;
+        self.end = start_vaddr + size;
+        self.free_lists = [None; MAX_ORDER + 1];
+        self.total_pages = size / PAGE_SIZE;
+        self.used_pages = 0;
+
+        // Carve the region into maximal aligned power-of-two blocks and seed
+        // each one onto the free list of its order.
+        let mut addr = start_vaddr;
+        while addr + PAGE_SIZE <= self.end {
+            let offset_pages = (addr - self.base) / PAGE_SIZE;
+            let remaining_pages = (self.end - addr) / PAGE_SIZE;
+            let mut order = MAX_ORDER;
+            loop {
+                let blk = 1usize << order;
+                let aligned = offset_pages & (blk - 1) == 0;
+                if blk <= remaining_pages && aligned {
+                    break;
+                }
+                order -= 1;
+            }
+            // SAFETY: `addr` is an aligned, in-region block of exactly `order`.
+            unsafe { self.push(order, addr) };
+            addr += (1usize << order) * PAGE_SIZE;
+        }
+    }
+
+    fn add_memory(&mut self, _start_vaddr: usize, _size: usize) -> AllocResult {
+        Err(AllocError::NoMemory)
+    }
+}
+
+impl<const PAGE_SIZE: usize> PageAllocator for BuddyPageAllocator<PAGE_SIZE> {
+    const PAGE_SIZE: usize = PAGE_SIZE;
+
+    fn alloc_pages(&mut self, num_pages: usize, align_pow2: usize) -> AllocResult<usize> {
+        if num_pages == 0 {
+            return Err(AllocError::InvalidParam);
+        }
+        let align = max(PAGE_SIZE, 1usize << align_pow2);
+        if !align.is_power_of_two() {
+            return Err(AllocError::InvalidParam);
+        }
+        // Buddy boundaries are defined relative to `base` and the offset is
+        // aligned below, so only an alignment `base` itself satisfies is met.
+        if self.base % align != 0 {
+            return Err(AllocError::NoMemory);
+        }
+        // The allocated order depends only on `num_pages`, so `dealloc_pages`
+        // can re-derive it the same way. Alignment is satisfied by picking an
+        // aligned order-`want` sub-block, not by inflating the order.
+        let want = Self::order_for(num_pages);
+        if want > MAX_ORDER {
+            return Err(AllocError::NoMemory);
+        }
+        let step = (1usize << want) * PAGE_SIZE;
+        // The sub-block must be aligned both to its own order (so buddy
+        // arithmetic stays valid) and to the caller's `align`. Both are powers
+        // of two, so aligning the base-relative offset to their max satisfies
+        // each; since `base % align == 0`, the absolute address is aligned too.
+        let granule = max(align, step);
+
+        // Scan free orders from `want` upward for a block that contains an
+        // aligned order-`want` sub-block, then split down toward it.
+        for order in want..=MAX_ORDER {
+            let mut node = self.free_lists[order];
+            while let Some(nn) = node {
+                let block = nn.as_ptr() as usize;
+                let block_end = block + (1usize << order) * PAGE_SIZE;
+                let target = self.base + Self::align_up(block - self.base, granule);
+                if target + step <= block_end {
+                    self.remove(order, block);
+                    // SAFETY: `block` is the order-`order` block we just
+                    // removed; `target` is an aligned order-`want` sub-block.
+                    unsafe { self.split_to(order, block, want, target) };
+                    self.used_pages += 1usize << want;
+                    return Ok(target);
+                }
+                // SAFETY: `nn` is a live node on the free list.
+                node = unsafe { nn.as_ref().next };
+            }
+        }
+        Err(AllocError::NoMemory)
+    }
+
+    fn dealloc_pages(&mut self, pos: usize, num_pages: usize) {
+        let mut order = Self::order_for(num_pages);
+        let mut addr = pos;
+        self.used_pages = self.used_pages.saturating_sub(1usize << order);
+        // Coalesce upward while the buddy is free and of the same order.
+        while order < MAX_ORDER {
+            let buddy = self.buddy_of(order, addr);
+            if !self.remove(order, buddy) {
+                break;
+            }
+            addr = addr.min(buddy);
+            order += 1;
+        }
+        // SAFETY: `addr` is a block we previously handed out (or a coalesced
+        // superset of one), now returning to the free list.
+        unsafe { self.push(order, addr) };
+    }
+
+    #[inline]
+    fn total_pages(&self) -> usize {
+        self.total_pages
+    }
+
+    #[inline]
+    fn used_pages(&self) -> usize {
+        self.used_pages
+    }
+
+    #[inline]
+    fn available_pages(&self) -> usize {
+        self.total_pages - self.used_pages
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use super::*;
+    use allocator::PageAllocator;
+    use std::alloc::{alloc, dealloc, Layout};
+
+    const PAGE: usize = 4096;
+
+    /// A writable, `PAGE`-granular backing region for the allocator under test.
+    struct Arena {
+        ptr: *mut u8,
+        layout: Layout,
+    }
+
+    impl Arena {
+        fn new(pages: usize) -> Self {
+            // Align the backing store generously so buddy/absolute alignment
+            // assertions are meaningful.
+            let layout = Layout::from_size_align(pages * PAGE, 1 << 21).unwrap();
+            let ptr = unsafe { alloc(layout) };
+            assert!(!ptr.is_null());
+            Self { ptr, layout }
+        }
+
+        fn base(&self) -> usize {
+            self.ptr as usize
+        }
+    }
+
+    impl Drop for Arena {
+        fn drop(&mut self) {
+            unsafe { dealloc(self.ptr, self.layout) };
+        }
+    }
+
+    #[test]
+    fn split_and_coalesce_restores_full_region() {
+        let arena = Arena::new(8);
+        let mut a = BuddyPageAllocator::<PAGE>::new();
+        a.init(arena.base(), 8 * PAGE);
+        assert_eq!(a.available_pages(), 8);
+
+        // Two single pages come from splitting the order-3 block.
+        let p0 = a.alloc_pages(1, 0).unwrap();
+        let p1 = a.alloc_pages(1, 0).unwrap();
+        assert_ne!(p0, p1);
+        assert_eq!(a.used_pages(), 2);
+
+        // Freeing both must coalesce all the way back to one order-3 block,
+        // so a 4-page request then succeeds.
+        a.dealloc_pages(p0, 1);
+        a.dealloc_pages(p1, 1);
+        assert_eq!(a.available_pages(), 8);
+        let big = a.alloc_pages(4, 0).unwrap();
+        assert_eq!(a.used_pages(), 4);
+        a.dealloc_pages(big, 4);
+        assert_eq!(a.available_pages(), 8);
+    }
+
+    #[test]
+    fn honors_absolute_alignment() {
+        let arena = Arena::new(8);
+        let mut a = BuddyPageAllocator::<PAGE>::new();
+        a.init(arena.base(), 8 * PAGE);
+
+        // Request a single page aligned to 4 * PAGE.
+        let align_pow2 = (4 * PAGE).trailing_zeros() as usize;
+        let p = a.alloc_pages(1, align_pow2).unwrap();
+        assert_eq!(p % (4 * PAGE), 0);
+
+        // Freeing re-derives order 0 from num_pages and must restore the region.
+        a.dealloc_pages(p, 1);
+        assert_eq!(a.available_pages(), 8);
+    }
+
+    #[test]
+    fn alloc_with_unaligned_base() {
+        // Start the managed region one page into a 2 MiB-aligned buffer so the
+        // base is page-aligned but not order-granule aligned: buddy boundaries
+        // must be computed relative to `base`, not absolutely.
+        let arena = Arena::new(9);
+        let mut a = BuddyPageAllocator::<PAGE>::new();
+        a.init(arena.base() + PAGE, 8 * PAGE);
+
+        // An order-1 (2-page) request must not panic and must land on a
+        // base-relative buddy boundary.
+        let p = a.alloc_pages(2, 0).unwrap();
+        assert_eq!((p - (arena.base() + PAGE)) % (2 * PAGE), 0);
+        a.dealloc_pages(p, 2);
+        assert_eq!(a.available_pages(), 8);
+    }
+}
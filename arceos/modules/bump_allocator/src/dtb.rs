@@ -0,0 +1,424 @@
+//! Minimal flattened-device-tree (FDT/DTB) reader.
+//!
+//! Only the pieces the early allocator needs are implemented: walking the
+//! `/memory` nodes for their `reg` ranges (honoring `#address-cells` /
+//! `#size-cells`) and collecting reserved ranges from the memory-reservation
+//! block and `/reserved-memory`.
+
+/// Maximum number of RAM banks / reserved ranges we track.
+pub const MAX_REGIONS: usize = 16;
+
+const FDT_MAGIC: u32 = 0xd00d_feed;
+const FDT_BEGIN_NODE: u32 = 0x1;
+const FDT_END_NODE: u32 = 0x2;
+const FDT_PROP: u32 = 0x3;
+const FDT_NOP: u32 = 0x4;
+const FDT_END: u32 = 0x9;
+
+/// A physical address range, in bytes.
+#[derive(Clone, Copy)]
+pub struct Region {
+    pub start: usize,
+    pub size: usize,
+}
+
+/// A fixed-capacity list of [`Region`]s.
+pub struct Regions {
+    buf: [Region; MAX_REGIONS],
+    len: usize,
+}
+
+impl Regions {
+    const fn new() -> Self {
+        Self {
+            buf: [Region { start: 0, size: 0 }; MAX_REGIONS],
+            len: 0,
+        }
+    }
+
+    fn push(&mut self, start: usize, size: usize) {
+        if size != 0 && self.len < MAX_REGIONS {
+            self.buf[self.len] = Region { start, size };
+            self.len += 1;
+        }
+    }
+
+    #[inline]
+    pub fn as_slice(&self) -> &[Region] {
+        &self.buf[..self.len]
+    }
+}
+
+/// A parsed device tree blob borrowing the firmware-provided bytes.
+pub struct Fdt<'a> {
+    struct_block: &'a [u8],
+    strings: &'a [u8],
+    rsvmap: &'a [u8],
+}
+
+impl<'a> Fdt<'a> {
+    /// Parse the FDT header at `dtb_ptr`.
+    ///
+    /// # Safety
+    /// `dtb_ptr` must point at a valid flattened device tree that stays alive
+    /// for the lifetime of the returned [`Fdt`].
+    pub unsafe fn new(dtb_ptr: *const u8) -> Option<Self> {
+        let header = core::slice::from_raw_parts(dtb_ptr, 40);
+        if be32(header, 0) != FDT_MAGIC {
+            return None;
+        }
+        let total = be32(header, 4) as usize;
+        let off_struct = be32(header, 8) as usize;
+        let off_strings = be32(header, 12) as usize;
+        let off_rsvmap = be32(header, 16) as usize;
+        let size_strings = be32(header, 32) as usize;
+        let size_struct = be32(header, 36) as usize;
+        // Validate every header-supplied offset against `total` so a truncated
+        // or malformed blob returns `None` instead of panicking on a slice.
+        if total < 40
+            || off_struct.checked_add(size_struct)? > total
+            || off_strings.checked_add(size_strings)? > total
+            || off_rsvmap > total
+        {
+            return None;
+        }
+        let blob = core::slice::from_raw_parts(dtb_ptr, total);
+        Some(Self {
+            struct_block: &blob[off_struct..off_struct + size_struct],
+            strings: &blob[off_strings..off_strings + size_strings],
+            rsvmap: &blob[off_rsvmap..],
+        })
+    }
+
+    /// Collect the usable RAM banks described by the `/memory` nodes.
+    pub fn memory_banks(&self) -> Regions {
+        let mut banks = Regions::new();
+        let b = self.struct_block;
+        let mut i = 0;
+        // Cells default to 2 at the root per the FDT spec.
+        let mut addr_cells = 2usize;
+        let mut size_cells = 2usize;
+        let mut depth = 0usize;
+        let mut in_memory = false;
+        while i + 4 <= b.len() {
+            let token = be32(b, i);
+            i += 4;
+            match token {
+                FDT_BEGIN_NODE => {
+                    let name = cstr(&b[i..]);
+                    i += align4(name.len() + 1);
+                    depth += 1;
+                    // `/memory` or `memory@<addr>` directly under the root.
+                    in_memory = depth == 2
+                        && (name == b"memory" || name.starts_with(b"memory@"));
+                }
+                FDT_END_NODE => {
+                    depth -= 1;
+                    in_memory = false;
+                }
+                FDT_PROP => {
+                    let len = be32(b, i) as usize;
+                    let nameoff = be32(b, i + 4) as usize;
+                    let data = &b[i + 8..i + 8 + len];
+                    i += 8 + align4(len);
+                    let pname = cstr(&self.strings[nameoff..]);
+                    if depth == 1 && pname == b"#address-cells" {
+                        addr_cells = be32(data, 0) as usize;
+                    } else if depth == 1 && pname == b"#size-cells" {
+                        size_cells = be32(data, 0) as usize;
+                    } else if in_memory && pname == b"reg" {
+                        read_reg(data, addr_cells, size_cells, &mut banks);
+                    }
+                }
+                FDT_NOP => {}
+                FDT_END => break,
+                _ => break,
+            }
+        }
+        banks
+    }
+
+    /// Collect reserved ranges from both the legacy memory-reservation block
+    /// and the `/reserved-memory` node (where modern U-Boot/OpenSBI place their
+    /// carveouts).
+    pub fn reserved(&self) -> Regions {
+        let mut out = Regions::new();
+
+        // 1. The fixed-format memory-reservation block.
+        let mut i = 0;
+        while i + 16 <= self.rsvmap.len() {
+            let addr = be64(self.rsvmap, i) as usize;
+            let size = be64(self.rsvmap, i + 8) as usize;
+            i += 16;
+            if addr == 0 && size == 0 {
+                break;
+            }
+            out.push(addr, size);
+        }
+
+        // 2. The `/reserved-memory` node: each child carries a `reg` range,
+        // decoded with that node's own `#address-cells`/`#size-cells`.
+        let b = self.struct_block;
+        let mut i = 0;
+        let mut depth = 0usize;
+        let mut in_rm = false;
+        let mut rm_addr_cells = 2usize;
+        let mut rm_size_cells = 2usize;
+        while i + 4 <= b.len() {
+            let token = be32(b, i);
+            i += 4;
+            match token {
+                FDT_BEGIN_NODE => {
+                    let name = cstr(&b[i..]);
+                    i += align4(name.len() + 1);
+                    depth += 1;
+                    if depth == 2 && name == b"reserved-memory" {
+                        in_rm = true;
+                        rm_addr_cells = 2;
+                        rm_size_cells = 2;
+                    }
+                }
+                FDT_END_NODE => {
+                    if in_rm && depth == 2 {
+                        in_rm = false;
+                    }
+                    depth -= 1;
+                }
+                FDT_PROP => {
+                    let len = be32(b, i) as usize;
+                    let nameoff = be32(b, i + 4) as usize;
+                    let data = &b[i + 8..i + 8 + len];
+                    i += 8 + align4(len);
+                    let pname = cstr(&self.strings[nameoff..]);
+                    if in_rm && depth == 2 && pname == b"#address-cells" {
+                        rm_addr_cells = be32(data, 0) as usize;
+                    } else if in_rm && depth == 2 && pname == b"#size-cells" {
+                        rm_size_cells = be32(data, 0) as usize;
+                    } else if in_rm && depth == 3 && pname == b"reg" {
+                        read_reg(data, rm_addr_cells, rm_size_cells, &mut out);
+                    }
+                }
+                FDT_NOP => {}
+                FDT_END => break,
+                _ => break,
+            }
+        }
+        out
+    }
+}
+
+/// Decode `reg` as `<address, size>` pairs of the given cell widths.
+fn read_reg(data: &[u8], addr_cells: usize, size_cells: usize, out: &mut Regions) {
+    let pair = (addr_cells + size_cells) * 4;
+    let mut i = 0;
+    while i + pair <= data.len() {
+        let addr = read_cells(&data[i..], addr_cells);
+        let size = read_cells(&data[i + addr_cells * 4..], size_cells);
+        out.push(addr as usize, size as usize);
+        i += pair;
+    }
+}
+
+#[inline]
+fn read_cells(data: &[u8], cells: usize) -> u64 {
+    match cells {
+        1 => be32(data, 0) as u64,
+        _ => be64(data, 0),
+    }
+}
+
+#[inline]
+fn be32(b: &[u8], off: usize) -> u32 {
+    u32::from_be_bytes([b[off], b[off + 1], b[off + 2], b[off + 3]])
+}
+
+#[inline]
+fn be64(b: &[u8], off: usize) -> u64 {
+    ((be32(b, off) as u64) << 32) | be32(b, off + 4) as u64
+}
+
+/// The NUL-terminated string starting at `b`, excluding the NUL.
+#[inline]
+fn cstr(b: &[u8]) -> &[u8] {
+    let end = b.iter().position(|&c| c == 0).unwrap_or(b.len());
+    &b[..end]
+}
+
+#[inline]
+fn align4(n: usize) -> usize {
+    (n + 3) & !3
+}
+
+
+/// Device-tree blob builder shared by the `dtb` and `EarlyAllocator` tests.
+#[cfg(test)]
+pub(crate) mod build {
+    extern crate std;
+    use super::*;
+    use std::format;
+    use std::vec::Vec;
+
+    struct Strings {
+        buf: Vec<u8>,
+    }
+    impl Strings {
+        fn new() -> Self {
+            Self { buf: Vec::new() }
+        }
+        fn intern(&mut self, s: &str) -> u32 {
+            let off = self.buf.len() as u32;
+            self.buf.extend_from_slice(s.as_bytes());
+            self.buf.push(0);
+            off
+        }
+    }
+
+    fn push_u32(v: &mut Vec<u8>, x: u32) {
+        v.extend_from_slice(&x.to_be_bytes());
+    }
+    fn push_u64(v: &mut Vec<u8>, x: u64) {
+        v.extend_from_slice(&x.to_be_bytes());
+    }
+    fn push_name(v: &mut Vec<u8>, name: &str) {
+        v.extend_from_slice(name.as_bytes());
+        v.push(0);
+        while v.len() % 4 != 0 {
+            v.push(0);
+        }
+    }
+    fn push_prop(v: &mut Vec<u8>, nameoff: u32, data: &[u8]) {
+        push_u32(v, FDT_PROP);
+        push_u32(v, data.len() as u32);
+        push_u32(v, nameoff);
+        v.extend_from_slice(data);
+        while v.len() % 4 != 0 {
+            v.push(0);
+        }
+    }
+
+    /// Assemble a spec-shaped device tree: one `/memory@` node per `banks`
+    /// entry, the `rsvmap` entries in the reservation block, and one
+    /// `/reserved-memory` child per `reserved_memory` entry. All cells are 2.
+    pub(crate) fn dtb(
+        banks: &[(u64, u64)],
+        rsvmap: &[(u64, u64)],
+        reserved_memory: &[(u64, u64)],
+    ) -> Vec<u8> {
+        let mut s = Strings::new();
+        let ac = s.intern("#address-cells");
+        let sc = s.intern("#size-cells");
+        let dt = s.intern("device_type");
+        let reg = s.intern("reg");
+
+        let mut st = Vec::new();
+        push_u32(&mut st, FDT_BEGIN_NODE);
+        push_name(&mut st, "");
+        push_prop(&mut st, ac, &2u32.to_be_bytes());
+        push_prop(&mut st, sc, &2u32.to_be_bytes());
+
+        for (start, size) in banks {
+            push_u32(&mut st, FDT_BEGIN_NODE);
+            push_name(&mut st, &format!("memory@{:x}", start));
+            push_prop(&mut st, dt, b"memory\0");
+            let mut regdata = Vec::new();
+            push_u64(&mut regdata, *start);
+            push_u64(&mut regdata, *size);
+            push_prop(&mut st, reg, &regdata);
+            push_u32(&mut st, FDT_END_NODE);
+        }
+
+        if !reserved_memory.is_empty() {
+            push_u32(&mut st, FDT_BEGIN_NODE);
+            push_name(&mut st, "reserved-memory");
+            push_prop(&mut st, ac, &2u32.to_be_bytes());
+            push_prop(&mut st, sc, &2u32.to_be_bytes());
+            for (start, size) in reserved_memory {
+                push_u32(&mut st, FDT_BEGIN_NODE);
+                push_name(&mut st, &format!("buf@{:x}", start));
+                let mut rm = Vec::new();
+                push_u64(&mut rm, *start);
+                push_u64(&mut rm, *size);
+                push_prop(&mut st, reg, &rm);
+                push_u32(&mut st, FDT_END_NODE);
+            }
+            push_u32(&mut st, FDT_END_NODE);
+        }
+
+        push_u32(&mut st, FDT_END_NODE); // close root
+        push_u32(&mut st, FDT_END);
+
+        let mut rsv = Vec::new();
+        for (start, size) in rsvmap {
+            push_u64(&mut rsv, *start);
+            push_u64(&mut rsv, *size);
+        }
+        push_u64(&mut rsv, 0);
+        push_u64(&mut rsv, 0);
+
+        let off_rsv = 40usize;
+        let off_struct = off_rsv + rsv.len();
+        let off_strings = off_struct + st.len();
+        let total = off_strings + s.buf.len();
+
+        let mut blob = Vec::new();
+        push_u32(&mut blob, FDT_MAGIC);
+        push_u32(&mut blob, total as u32);
+        push_u32(&mut blob, off_struct as u32);
+        push_u32(&mut blob, off_strings as u32);
+        push_u32(&mut blob, off_rsv as u32);
+        push_u32(&mut blob, 17); // version
+        push_u32(&mut blob, 16); // last_comp_version
+        push_u32(&mut blob, 0); // boot_cpuid_phys
+        push_u32(&mut blob, s.buf.len() as u32);
+        push_u32(&mut blob, st.len() as u32);
+        blob.extend_from_slice(&rsv);
+        blob.extend_from_slice(&st);
+        blob.extend_from_slice(&s.buf);
+        blob
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use super::*;
+
+    #[test]
+    fn parses_memory_banks() {
+        let blob = build::dtb(&[(0x8000_0000, 0x1000_0000)], &[(0x1000, 0x1000)], &[]);
+        let fdt = unsafe { Fdt::new(blob.as_ptr()) }.unwrap();
+        let banks = fdt.memory_banks();
+        let banks = banks.as_slice();
+        assert_eq!(banks.len(), 1);
+        assert_eq!(banks[0].start, 0x8000_0000);
+        assert_eq!(banks[0].size, 0x1000_0000);
+    }
+
+    #[test]
+    fn parses_reservations_from_both_sources() {
+        let blob = build::dtb(
+            &[(0x8000_0000, 0x1000_0000)],
+            &[(0x1000, 0x1000)],
+            &[(0x8800_0000, 0x1000)],
+        );
+        let fdt = unsafe { Fdt::new(blob.as_ptr()) }.unwrap();
+        let reserved = fdt.reserved();
+        let reserved = reserved.as_slice();
+        // One from the rsvmap block, one from /reserved-memory.
+        assert_eq!(reserved.len(), 2);
+        assert_eq!(reserved[0].start, 0x1000);
+        assert_eq!(reserved[0].size, 0x1000);
+        assert_eq!(reserved[1].start, 0x8800_0000);
+        assert_eq!(reserved[1].size, 0x1000);
+    }
+
+    #[test]
+    fn rejects_truncated_blob() {
+        let mut blob = build::dtb(&[(0x8000_0000, 0x1000_0000)], &[], &[]);
+        // Halve the header's totalsize so the struct/strings offsets point
+        // past the end the header claims.
+        let half = (blob.len() / 2) as u32;
+        blob[4..8].copy_from_slice(&half.to_be_bytes());
+        assert!(unsafe { Fdt::new(blob.as_ptr()) }.is_none());
+    }
+}
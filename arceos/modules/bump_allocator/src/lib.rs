@@ -1,5 +1,26 @@
 #![no_std]
 
+mod bitmap;
+mod buddy;
+mod dtb;
+mod slab;
+
+pub use bitmap::BitmapPageAllocator;
+pub use buddy::BuddyPageAllocator;
+pub use slab::SlabByteAllocator;
+
+/// Maximum number of extra RAM banks appended via [`BaseAllocator::add_memory`],
+/// beyond the primary arena.
+const MAX_EXTRA_BANKS: usize = 8;
+
+/// An extra forward-growing byte bump region, appended after the primary arena.
+#[derive(Clone, Copy)]
+struct ExtraBank {
+    start: usize,
+    b_pos: usize,
+    end: usize,
+}
+
 use allocator::{AllocResult, BaseAllocator, ByteAllocator, PageAllocator};
 use core::alloc::Layout;
 use core::cmp::{max, min};
@@ -27,6 +48,10 @@ pub struct EarlyAllocator<const PAGE_SIZE: usize> {
     p_pos: usize,
     /// number of active byte allocations
     count: usize,
+    /// extra byte banks registered via `add_memory` / `init_from_dtb`
+    extra: [ExtraBank; MAX_EXTRA_BANKS],
+    /// number of populated entries in `extra`
+    extra_len: usize,
 }
 
 impl<const PAGE_SIZE: usize> EarlyAllocator<PAGE_SIZE> {
@@ -38,9 +63,98 @@ impl<const PAGE_SIZE: usize> EarlyAllocator<PAGE_SIZE> {
             b_pos: 0,
             p_pos: 0,
             count: 0,
+            extra: [ExtraBank {
+                start: 0,
+                b_pos: 0,
+                end: 0,
+            }; MAX_EXTRA_BANKS],
+            extra_len: 0,
         }
     }
 
+    /// Initialize the allocator from a flattened device tree.
+    ///
+    /// Walks the FDT `/memory` nodes, subtracts the ranges listed in the
+    /// memory-reservation block and under `/reserved-memory`, then uses the
+    /// largest remaining bank as the
+    /// primary double-ended arena and appends the rest via [`add_memory`].
+    ///
+    /// [`add_memory`]: BaseAllocator::add_memory
+    ///
+    /// # Safety
+    /// `dtb_ptr` must point at a valid device tree blob that outlives this call.
+    pub unsafe fn init_from_dtb(&mut self, dtb_ptr: *const u8) -> AllocResult {
+        let fdt = dtb::Fdt::new(dtb_ptr).ok_or(allocator::AllocError::InvalidParam)?;
+        let banks = fdt.memory_banks();
+        let reserved = fdt.reserved();
+
+        // Split every bank around the reserved ranges, keeping only usable gaps.
+        let mut usable: [dtb::Region; dtb::MAX_REGIONS] = [dtb::Region { start: 0, size: 0 };
+            dtb::MAX_REGIONS];
+        let mut usable_len = 0;
+        for bank in banks.as_slice() {
+            let mut cursor = bank.start;
+            let bank_end = bank.start + bank.size;
+            loop {
+                // Find the earliest-starting reserved range that intersects
+                // [cursor, bank_end). Compare the *clamped* starts so unsorted
+                // or overlapping carveouts are ordered consistently.
+                let mut cut: Option<(usize, usize)> = None;
+                for r in reserved.as_slice() {
+                    let cs = r.start.max(cursor);
+                    let ce = (r.start + r.size).min(bank_end);
+                    if ce > cs {
+                        match cut {
+                            Some((best_s, _)) if best_s <= cs => {}
+                            _ => cut = Some((cs, ce)),
+                        }
+                    }
+                }
+                match cut {
+                    Some((s, e)) => {
+                        if s > cursor && usable_len < dtb::MAX_REGIONS {
+                            usable[usable_len] = dtb::Region {
+                                start: cursor,
+                                size: s - cursor,
+                            };
+                            usable_len += 1;
+                        }
+                        cursor = e;
+                        if cursor >= bank_end {
+                            break;
+                        }
+                    }
+                    None => {
+                        if bank_end > cursor && usable_len < dtb::MAX_REGIONS {
+                            usable[usable_len] = dtb::Region {
+                                start: cursor,
+                                size: bank_end - cursor,
+                            };
+                            usable_len += 1;
+                        }
+                        break;
+                    }
+                }
+            }
+        }
+
+        let usable = &usable[..usable_len];
+        // Pick the largest usable bank as the primary double-ended arena.
+        let primary = usable
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, r)| r.size)
+            .map(|(i, _)| i)
+            .ok_or(allocator::AllocError::NoMemory)?;
+        self.init(usable[primary].start, usable[primary].size);
+        for (i, r) in usable.iter().enumerate() {
+            if i != primary {
+                self.add_memory(r.start, r.size)?;
+            }
+        }
+        Ok(())
+    }
+
     #[inline]
     fn align_up(addr: usize, align: usize) -> usize {
         let a = max(1, align);
@@ -52,6 +166,57 @@ impl<const PAGE_SIZE: usize> EarlyAllocator<PAGE_SIZE> {
         let a = max(1, align);
         addr & !(a - 1)
     }
+
+    /// Permanently mark `[addr, addr + size)` as unavailable before any
+    /// dynamic allocation hands it out.
+    ///
+    /// This lets the early allocator be initialized over a whole RAM bank and
+    /// then have firmware/kernel-occupied holes (the kernel image, the DTB,
+    /// MMIO windows, framebuffer) punched out safely. For the double-ended bump
+    /// design:
+    /// - a range abutting the byte region advances `b_pos` (and `start`, the
+    ///   point byte deallocation rewinds to) past it;
+    /// - a range abutting the page region lowers `p_pos`;
+    /// - an interior range splits the free window: the lower part `[b_pos, addr)`
+    ///   stays the primary double-ended arena (its `p_pos` lowered to `addr`),
+    ///   and the upper part `[addr + size, p_pos)` is re-registered as an extra
+    ///   byte bank via [`add_memory`], so no live memory is thrown away. (The
+    ///   upper part loses its page cursor, as extra banks are byte-only.)
+    ///
+    /// [`add_memory`]: BaseAllocator::add_memory
+    ///
+    /// Returns [`AllocError::MemoryOverlap`] if the range is already partly
+    /// allocated, or [`AllocError::InvalidParam`] if it falls outside the arena.
+    pub fn reserve(&mut self, addr: usize, size: usize) -> AllocResult {
+        if size == 0 {
+            return Ok(());
+        }
+        let r_end = addr.checked_add(size).ok_or(allocator::AllocError::InvalidParam)?;
+        if addr < self.start || r_end > self.end {
+            return Err(allocator::AllocError::InvalidParam);
+        }
+        // The free window is exactly [b_pos, p_pos); anything outside it has
+        // already been handed out.
+        if addr < self.b_pos || r_end > self.p_pos {
+            return Err(allocator::AllocError::MemoryOverlap);
+        }
+        if addr == self.b_pos {
+            self.b_pos = r_end;
+            // Move the byte region's reset point too, so freeing every byte
+            // allocation cannot rewind `b_pos` into the reserved range.
+            self.start = r_end;
+        } else if r_end == self.p_pos {
+            self.p_pos = addr;
+        } else {
+            // Interior hole: keep the lower part as the primary arena and hand
+            // the upper part to `add_memory` so neither cursor can return the
+            // hole, without discarding either side.
+            self.add_memory(r_end, self.p_pos - r_end)?;
+            self.p_pos = addr;
+            self.end = addr;
+        }
+        Ok(())
+    }
 }
 
 impl<const PAGE_SIZE: usize> BaseAllocator for EarlyAllocator<PAGE_SIZE> {
@@ -62,11 +227,22 @@ impl<const PAGE_SIZE: usize> BaseAllocator for EarlyAllocator<PAGE_SIZE> {
         self.b_pos = start_vaddr;
         self.p_pos = self.end;
         self.count = 0;
+        self.extra_len = 0;
     }
 
     #[inline]
-    fn add_memory(&mut self, _start_vaddr: usize, _size: usize) -> AllocResult {
-        // Early allocator doesn't support expanding; treat as success no-op
+    fn add_memory(&mut self, start_vaddr: usize, size: usize) -> AllocResult {
+        // Additional discontiguous banks become extra forward-growing byte
+        // regions; the primary arena keeps the double-ended page area.
+        if self.extra_len >= MAX_EXTRA_BANKS {
+            return Err(allocator::AllocError::NoMemory);
+        }
+        self.extra[self.extra_len] = ExtraBank {
+            start: start_vaddr,
+            b_pos: start_vaddr,
+            end: start_vaddr + size,
+        };
+        self.extra_len += 1;
         Ok(())
     }
 
@@ -74,34 +250,71 @@ impl<const PAGE_SIZE: usize> BaseAllocator for EarlyAllocator<PAGE_SIZE> {
 
 impl<const PAGE_SIZE: usize> ByteAllocator for EarlyAllocator<PAGE_SIZE> {
     #[inline]
-    fn total_bytes(&self) -> usize { self.end.saturating_sub(self.start) }
+    fn total_bytes(&self) -> usize {
+        let extra: usize = self.extra[..self.extra_len]
+            .iter()
+            .map(|b| b.end - b.start)
+            .sum();
+        self.end.saturating_sub(self.start) + extra
+    }
     #[inline]
     fn alloc(&mut self, layout: Layout) -> AllocResult<NonNull<u8>> {
         let size = max(layout.size(), 1);
         let align = max(layout.align(), 1);
         let start = Self::align_up(self.b_pos, align);
         let end = start.checked_add(size).ok_or(allocator::AllocError::NoMemory)?;
-        if end > self.p_pos { return Err(allocator::AllocError::NoMemory); }
-        self.b_pos = end;
-        self.count += 1;
-        // SAFETY: points into the managed region and non-null
-        Ok(unsafe { NonNull::new_unchecked(start as *mut u8) })
+        if end <= self.p_pos {
+            self.b_pos = end;
+            self.count += 1;
+            // SAFETY: points into the managed region and non-null
+            return Ok(unsafe { NonNull::new_unchecked(start as *mut u8) });
+        }
+        // Primary arena is exhausted; try the extra banks in order.
+        for bank in &mut self.extra[..self.extra_len] {
+            let start = Self::align_up(bank.b_pos, align);
+            let end = match start.checked_add(size) {
+                Some(e) => e,
+                None => continue,
+            };
+            if end <= bank.end {
+                bank.b_pos = end;
+                self.count += 1;
+                // SAFETY: points into a managed extra bank and non-null
+                return Ok(unsafe { NonNull::new_unchecked(start as *mut u8) });
+            }
+        }
+        Err(allocator::AllocError::NoMemory)
     }
 
     #[inline]
     fn dealloc(&mut self, _pos: NonNull<u8>, _layout: Layout) {
         if self.count > 0 { self.count -= 1; }
-        // When all byte allocations are freed, reclaim the byte area.
+        // When all byte allocations are freed, reclaim every byte region.
         if self.count == 0 {
             self.b_pos = self.start;
+            for bank in &mut self.extra[..self.extra_len] {
+                bank.b_pos = bank.start;
+            }
         }
     }
 
     #[inline]
-    fn used_bytes(&self) -> usize { self.b_pos.saturating_sub(self.start) }
+    fn used_bytes(&self) -> usize {
+        let extra: usize = self.extra[..self.extra_len]
+            .iter()
+            .map(|b| b.b_pos - b.start)
+            .sum();
+        self.b_pos.saturating_sub(self.start) + extra
+    }
 
     #[inline]
-    fn available_bytes(&self) -> usize { self.p_pos.saturating_sub(self.b_pos) }
+    fn available_bytes(&self) -> usize {
+        let extra: usize = self.extra[..self.extra_len]
+            .iter()
+            .map(|b| b.end - b.b_pos)
+            .sum();
+        self.p_pos.saturating_sub(self.b_pos) + extra
+    }
 }
 
 impl<const PAGE_SIZE: usize> PageAllocator for EarlyAllocator<PAGE_SIZE> {
@@ -133,3 +346,85 @@ impl<const PAGE_SIZE: usize> PageAllocator for EarlyAllocator<PAGE_SIZE> {
     #[inline]
     fn available_pages(&self) -> usize { (self.p_pos.saturating_sub(self.b_pos)) / PAGE_SIZE }
 }
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use super::*;
+    use core::alloc::Layout;
+
+    const PAGE: usize = 0x1000;
+    const BASE: usize = 0x8000_0000;
+    const SIZE: usize = 16 * PAGE;
+
+    fn new_alloc() -> EarlyAllocator<PAGE> {
+        let mut a = EarlyAllocator::<PAGE>::new();
+        a.init(BASE, SIZE);
+        a
+    }
+
+    #[test]
+    fn reserve_abutting_byte_region_advances_b_pos() {
+        let mut a = new_alloc();
+        a.reserve(BASE, 2 * PAGE).unwrap();
+        // The next byte allocation lands past the reserved range.
+        let p = a.alloc(Layout::from_size_align(8, 8).unwrap()).unwrap();
+        assert_eq!(p.as_ptr() as usize, BASE + 2 * PAGE);
+    }
+
+    #[test]
+    fn reserve_abutting_page_region_lowers_p_pos() {
+        let mut a = new_alloc();
+        a.reserve(BASE + SIZE - 2 * PAGE, 2 * PAGE).unwrap();
+        // The reserved top pages are no longer handed out.
+        let p = a.alloc_pages(1, 0).unwrap();
+        assert_eq!(p, BASE + SIZE - 3 * PAGE);
+    }
+
+    #[test]
+    fn reserve_interior_preserves_both_sides() {
+        let mut a = new_alloc();
+        // Punch a one-page hole in the middle.
+        a.reserve(BASE + 8 * PAGE, PAGE).unwrap();
+        // No live memory is lost: total stays all-but-the-hole.
+        assert_eq!(a.total_bytes(), SIZE - PAGE);
+        // The lower part still serves byte allocations from the base...
+        let lo = a.alloc(Layout::from_size_align(8, 8).unwrap()).unwrap();
+        assert_eq!(lo.as_ptr() as usize, BASE);
+        // ...and the upper part is reachable as an extra bank.
+        assert_eq!(a.available_bytes(), SIZE - PAGE - 8);
+    }
+
+    #[test]
+    fn init_from_dtb_splits_bank_around_interior_reservation() {
+        // A single 1 MiB bank with a one-page reservation in the middle.
+        let bank = (0x8000_0000u64, 0x10_0000u64);
+        let resv_start = 0x8008_0000u64;
+        let blob = crate::dtb::build::dtb(&[bank], &[], &[(resv_start, 0x1000)]);
+
+        let mut a = EarlyAllocator::<PAGE>::new();
+        unsafe { a.init_from_dtb(blob.as_ptr()) }.unwrap();
+
+        // The reservation splits the bank into a 0x80000-byte lower part and a
+        // 0x7F000-byte upper part; the larger lower part is the primary arena
+        // and the upper part is registered as one extra bank.
+        assert_eq!(a.total_bytes(), 0x10_0000 - 0x1000);
+        assert_eq!(a.extra_len, 1);
+        assert_eq!(a.start, 0x8000_0000);
+        assert_eq!(a.end, 0x8000_0000 + 0x8_0000);
+        assert_eq!(a.extra[0].start, (resv_start + 0x1000) as usize);
+
+        // Byte allocations start from the primary bank's base.
+        let p = a.alloc(Layout::from_size_align(8, 8).unwrap()).unwrap();
+        assert_eq!(p.as_ptr() as usize, 0x8000_0000);
+    }
+
+    #[test]
+    fn reserve_overlapping_allocated_fails() {
+        let mut a = new_alloc();
+        // Consume the first page of the byte region.
+        let _ = a.alloc(Layout::from_size_align(PAGE, 8).unwrap()).unwrap();
+        let err = a.reserve(BASE, PAGE).unwrap_err();
+        assert!(matches!(err, allocator::AllocError::MemoryOverlap));
+    }
+}
@@ -0,0 +1,247 @@
+use allocator::{AllocError, AllocResult, BaseAllocator, ByteAllocator};
+use core::alloc::Layout;
+use core::cmp::max;
+use core::ptr::NonNull;
+
+/// Power-of-two size classes, in bytes. Requests larger than the biggest class
+/// fall back to the bump path and are never recycled.
+const SIZE_CLASSES: [usize; 8] = [16, 32, 64, 128, 256, 512, 1024, 2048];
+
+/// Intrusive free-list node, stored in the first `usize` of a freed slot.
+struct FreeNode {
+    next: Option<NonNull<FreeNode>>,
+}
+
+/// Free-list byte allocator with per-size-class slab recycling.
+///
+/// Where [`EarlyAllocator`](crate::EarlyAllocator) can only reclaim its byte
+/// arena once *every* allocation is freed, this allocator keeps an intrusive
+/// free list per size class: freed blocks are pushed back onto their class's
+/// list and reused by later requests, so a single long-lived allocation no
+/// longer pins the whole arena. Fresh slots are bump-allocated from a
+/// forward-growing cursor, matching the early allocator's backing store.
+pub struct SlabByteAllocator {
+    start: usize,
+    end: usize,
+    /// next free address for fresh bump allocations (grows upward)
+    b_pos: usize,
+    free_lists: [Option<NonNull<FreeNode>>; SIZE_CLASSES.len()],
+}
+
+impl Default for SlabByteAllocator {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SlabByteAllocator {
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            start: 0,
+            end: 0,
+            b_pos: 0,
+            free_lists: [None; SIZE_CLASSES.len()],
+        }
+    }
+
+    #[inline]
+    fn align_up(addr: usize, align: usize) -> usize {
+        let a = max(1, align);
+        (addr + a - 1) & !(a - 1)
+    }
+
+    /// Unlink and return the first node in `class`'s free list whose address
+    /// satisfies `align`, scanning the whole list rather than just the head so
+    /// aligned slots deeper in the list are still reused.
+    fn take_aligned(&mut self, class: usize, align: usize) -> Option<NonNull<u8>> {
+        let mut link = &mut self.free_lists[class] as *mut Option<NonNull<FreeNode>>;
+        // SAFETY: we only dereference live nodes reachable from the list head.
+        unsafe {
+            while let Some(node) = *link {
+                if (node.as_ptr() as usize) % align == 0 {
+                    *link = node.as_ref().next;
+                    return Some(node.cast());
+                }
+                link = &mut (*node.as_ptr()).next;
+            }
+        }
+        None
+    }
+
+    /// Index of the smallest size class that fits `size`, or `None` if the
+    /// request is larger than any class.
+    #[inline]
+    fn class_for(size: usize) -> Option<usize> {
+        SIZE_CLASSES.iter().position(|&c| c >= size)
+    }
+}
+
+impl BaseAllocator for SlabByteAllocator {
+    #[inline]
+    fn init(&mut self, start_vaddr: usize, size: usize) {
+        self.start = start_vaddr;
+        self.end = start_vaddr + size;
+        self.b_pos = start_vaddr;
+        self.free_lists = [None; SIZE_CLASSES.len()];
+    }
+
+    #[inline]
+    fn add_memory(&mut self, _start_vaddr: usize, _size: usize) -> AllocResult {
+        Err(AllocError::NoMemory)
+    }
+}
+
+impl ByteAllocator for SlabByteAllocator {
+    #[inline]
+    fn total_bytes(&self) -> usize {
+        self.end.saturating_sub(self.start)
+    }
+
+    fn alloc(&mut self, layout: Layout) -> AllocResult<NonNull<u8>> {
+        let size = max(layout.size(), 1);
+        let align = max(layout.align(), 1);
+        if let Some(class) = Self::class_for(size) {
+            // Recycle the first free slot of this class that satisfies the
+            // requested alignment; only bump a fresh slot if none qualifies, so
+            // a misaligned head cannot hide aligned slots deeper in the list.
+            if let Some(node) = self.take_aligned(class, align) {
+                return Ok(node);
+            }
+            // Otherwise bump-allocate a fresh slot sized to the class.
+            let slot = max(SIZE_CLASSES[class], align);
+            let start = Self::align_up(self.b_pos, align);
+            let end = start.checked_add(slot).ok_or(AllocError::NoMemory)?;
+            if end > self.end {
+                return Err(AllocError::NoMemory);
+            }
+            self.b_pos = end;
+            // SAFETY: points into the managed region and non-null.
+            return Ok(unsafe { NonNull::new_unchecked(start as *mut u8) });
+        }
+
+        // Oversized request: bump-allocate directly, no recycling.
+        let start = Self::align_up(self.b_pos, align);
+        let end = start.checked_add(size).ok_or(AllocError::NoMemory)?;
+        if end > self.end {
+            return Err(AllocError::NoMemory);
+        }
+        self.b_pos = end;
+        // SAFETY: points into the managed region and non-null.
+        Ok(unsafe { NonNull::new_unchecked(start as *mut u8) })
+    }
+
+    fn dealloc(&mut self, pos: NonNull<u8>, layout: Layout) {
+        let size = max(layout.size(), 1);
+        // Oversized allocations have no class and cannot be recycled.
+        if let Some(class) = Self::class_for(size) {
+            let node = pos.as_ptr() as *mut FreeNode;
+            // SAFETY: `pos` was handed out for this class; the slot is at least
+            // `usize`-sized so storing the link is in bounds.
+            unsafe {
+                (*node).next = self.free_lists[class];
+            }
+            self.free_lists[class] = NonNull::new(node);
+        }
+    }
+
+    #[inline]
+    fn used_bytes(&self) -> usize {
+        self.b_pos.saturating_sub(self.start)
+    }
+
+    #[inline]
+    fn available_bytes(&self) -> usize {
+        self.end.saturating_sub(self.b_pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use super::*;
+    use allocator::ByteAllocator;
+    use std::alloc::{alloc, dealloc, Layout};
+
+    struct Arena {
+        ptr: *mut u8,
+        layout: Layout,
+    }
+
+    impl Arena {
+        fn new(size: usize) -> Self {
+            let layout = Layout::from_size_align(size, 4096).unwrap();
+            let ptr = unsafe { alloc(layout) };
+            assert!(!ptr.is_null());
+            Self { ptr, layout }
+        }
+        fn base(&self) -> usize {
+            self.ptr as usize
+        }
+    }
+
+    impl Drop for Arena {
+        fn drop(&mut self) {
+            unsafe { dealloc(self.ptr, self.layout) };
+        }
+    }
+
+    #[test]
+    fn recycles_same_class_slot() {
+        let arena = Arena::new(64 * 1024);
+        let mut a = SlabByteAllocator::new();
+        a.init(arena.base(), 64 * 1024);
+
+        let l = Layout::from_size_align(16, 8).unwrap();
+        let p0 = a.alloc(l).unwrap();
+        a.dealloc(p0, l);
+        // The freed 16-byte slot must be handed straight back.
+        let p1 = a.alloc(l).unwrap();
+        assert_eq!(p0.as_ptr(), p1.as_ptr());
+    }
+
+    #[test]
+    fn recycled_slot_honors_higher_alignment() {
+        let arena = Arena::new(64 * 1024);
+        let mut a = SlabByteAllocator::new();
+        a.init(arena.base(), 64 * 1024);
+
+        // Allocate two 16-byte slots so the second one starts at base+16,
+        // which is not 64-aligned, then free it.
+        let low = Layout::from_size_align(16, 8).unwrap();
+        let _p0 = a.alloc(low).unwrap();
+        let p1 = a.alloc(low).unwrap();
+        assert_ne!(p1.as_ptr() as usize % 64, 0);
+        a.dealloc(p1, low);
+
+        // A later 64-aligned request must not reuse the misaligned free slot.
+        let high = Layout::from_size_align(16, 64).unwrap();
+        let p2 = a.alloc(high).unwrap();
+        assert_eq!(p2.as_ptr() as usize % 64, 0);
+        assert_ne!(p2.as_ptr(), p1.as_ptr());
+    }
+
+    #[test]
+    fn reuses_aligned_slot_deeper_in_list() {
+        // Arrange for a 64-aligned slot to sit behind a misaligned head.
+        let arena = Arena::new(64 * 1024);
+        let mut a = SlabByteAllocator::new();
+        a.init(arena.base(), 64 * 1024);
+
+        let low = Layout::from_size_align(16, 8).unwrap();
+        let p0 = a.alloc(low).unwrap(); // base (64-aligned)
+        let p1 = a.alloc(low).unwrap(); // base + 16 (not 64-aligned)
+        assert_eq!(p0.as_ptr() as usize % 64, 0);
+        // Free p0 first, then p1, so the misaligned slot becomes the head.
+        a.dealloc(p0, low);
+        a.dealloc(p1, low);
+
+        // A 64-aligned request must skip the head and reuse p0, not bump.
+        let used_before = a.used_bytes();
+        let high = Layout::from_size_align(16, 64).unwrap();
+        let p2 = a.alloc(high).unwrap();
+        assert_eq!(p2.as_ptr(), p0.as_ptr());
+        assert_eq!(a.used_bytes(), used_before);
+    }
+}